@@ -0,0 +1,305 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use async_stream::try_stream;
+use cid::multihash::{Code, MultihashDigest};
+use cid::Cid;
+use futures::{AsyncRead, AsyncReadExt, Stream};
+use serde::Deserialize;
+
+use crate::encoding::decode_row;
+use crate::manifest::decode_manifest;
+use crate::{RowData, RowKey};
+
+/// The CAR header (roots + version), parsed once when a [`CarReader`] is constructed.
+///
+/// `salt`/`encryption` are only ever present on a header written with
+/// encryption-at-rest enabled; `CarReader` doesn't support decrypting rows,
+/// so [`CarReader::new`] rejects such headers instead of silently feeding
+/// ciphertext to [`decode_row`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct CarHeader {
+    pub roots: Vec<String>,
+    pub version: u64,
+    #[serde(default)]
+    pub salt: Option<Vec<u8>>,
+    #[serde(default)]
+    pub encryption: Option<String>,
+}
+
+/// An async, incremental reader over a CAR file's rows.
+///
+/// Unlike [`crate::read_all_rows_from_car_reader`], which buffers every row
+/// into a `Vec` up front, `CarReader` parses the header once in [`CarReader::new`]
+/// and then decodes one row at a time from [`CarReader::next_row`], reusing a
+/// single scratch buffer across calls. This keeps memory bounded to the
+/// largest single block, regardless of how large the overall CAR is, which
+/// matters when streaming multi-gigabyte CARs out of HDFS or object storage.
+pub struct CarReader<R> {
+    inner: R,
+    header: CarHeader,
+    /// Child row-block CIDs from the manifest, in write order; `next_row`
+    /// checks each row block it reads against `manifest_cids[row_index]`.
+    manifest_cids: Vec<Cid>,
+    row_index: usize,
+    scratch: Vec<u8>,
+    done: bool,
+}
+
+impl<R: AsyncRead + Unpin> CarReader<R> {
+    /// Read and parse the CAR header from `inner`, then read and verify the
+    /// manifest block its single root points at, and return a reader
+    /// positioned at the start of the first row block.
+    pub async fn new(mut inner: R) -> Result<Self> {
+        let header_bytes = read_prefixed_block(&mut inner).await?;
+        let header: CarHeader = serde_cbor::from_slice(&header_bytes)?;
+
+        if header.encryption.is_some() {
+            return Err(anyhow::anyhow!(
+                "CarReader does not support encrypted CAR files; read it with \
+                 crate::read_all_rows_from_car_reader and a passphrase instead"
+            ));
+        }
+
+        let expected_manifest_cid: Cid = header
+            .roots
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("No roots found in CAR file"))?
+            .parse()?;
+
+        let manifest_block = read_prefixed_block(&mut inner).await?;
+        let (_stored_cid, manifest_data) = split_cid_and_data(&manifest_block)?;
+        let actual_manifest_cid = recompute_cid(&manifest_data);
+        if actual_manifest_cid != expected_manifest_cid {
+            return Err(anyhow::anyhow!(
+                "CID mismatch for manifest: expected {}, got {}",
+                expected_manifest_cid, actual_manifest_cid
+            ));
+        }
+        let manifest_cids = decode_manifest(&manifest_data)?.cids()?;
+
+        Ok(CarReader {
+            inner,
+            header,
+            manifest_cids,
+            row_index: 0,
+            scratch: Vec::new(),
+            done: false,
+        })
+    }
+
+    /// The CAR header parsed when this reader was constructed.
+    pub fn header(&self) -> &CarHeader {
+        &self.header
+    }
+
+    /// Read the next row, decoding it lazily from a single reused scratch
+    /// buffer, and verifying its CID against the manifest.
+    ///
+    /// Returns `Ok(None)` on a clean EOF at a block boundary (i.e. no bytes of
+    /// a new block's length prefix have been read); errors if fewer row
+    /// blocks were actually read than the manifest lists.
+    pub async fn next_row(&mut self) -> Result<Option<(RowKey, RowData)>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let length = match read_varint(&mut self.inner).await? {
+            Some(length) => length,
+            None => {
+                self.done = true;
+                if self.row_index != self.manifest_cids.len() {
+                    return Err(anyhow::anyhow!(
+                        "manifest lists {} row block(s) but the file only has {}",
+                        self.manifest_cids.len(),
+                        self.row_index
+                    ));
+                }
+                return Ok(None);
+            }
+        };
+
+        self.scratch.clear();
+        self.scratch.resize(length as usize, 0);
+        self.inner.read_exact(&mut self.scratch).await?;
+
+        let mut cursor = Cursor::new(&self.scratch[..]);
+        let _stored_cid = Cid::read_bytes(&mut cursor)?;
+        let pos = cursor.position() as usize;
+        let data = &self.scratch[pos..];
+
+        let expected_cid = self
+            .manifest_cids
+            .get(self.row_index)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("CAR file has more row blocks than its manifest lists"))?;
+        let actual_cid = recompute_cid(data);
+        if actual_cid != expected_cid {
+            return Err(anyhow::anyhow!(
+                "CID mismatch for row block {}: expected {}, got {}",
+                self.row_index, expected_cid, actual_cid
+            ));
+        }
+
+        let (row_key, row_data) = decode_row(data)?;
+        self.row_index += 1;
+        Ok(Some((row_key, row_data)))
+    }
+}
+
+impl<R: AsyncRead + Unpin + Send + 'static> CarReader<R> {
+    /// Turn this reader into a [`futures::Stream`] of rows, so callers can
+    /// `while let Some(row) = stream.next().await` instead of driving
+    /// [`CarReader::next_row`] by hand.
+    pub fn into_stream(mut self) -> impl Stream<Item = Result<(RowKey, RowData)>> {
+        try_stream! {
+            while let Some(row) = self.next_row().await? {
+                yield row;
+            }
+        }
+    }
+}
+
+/// Splits a block into `(Cid, data)` given `block` (already excludes the
+/// varint length prefix). The returned `Cid` is just what's physically
+/// stored in the block, not verified; callers must check `data` against an
+/// independently-sourced expected `Cid` (e.g. via [`recompute_cid`]).
+fn split_cid_and_data(block: &[u8]) -> Result<(Cid, Vec<u8>)> {
+    let mut cursor = Cursor::new(block);
+    let cid = Cid::read_bytes(&mut cursor)?;
+    let pos = cursor.position() as usize;
+    Ok((cid, block[pos..].to_vec()))
+}
+
+/// Recomputes the `Cid` that `data` actually hashes to (dag-cbor, SHA2-256),
+/// the same way [`crate::encoding::encode_row`]/[`crate::manifest::encode_manifest`]
+/// compute it when writing. The CID bytes physically stored in a block are
+/// just as untrusted as the rest of it, so this is the only check that
+/// detects corruption confined to a block's data portion.
+fn recompute_cid(data: &[u8]) -> Cid {
+    let hash = Code::Sha2_256.digest(data);
+    Cid::new_v1(0x71, hash)
+}
+
+/// Reads a varint-prefixed block. Errors if the stream is already at EOF
+/// (no bytes of the length prefix could be read) or the block is truncated.
+async fn read_prefixed_block<R: AsyncRead + Unpin>(r: &mut R) -> Result<Vec<u8>> {
+    let length = read_varint(r)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Unexpected EOF reading block length"))?;
+    let mut buf = vec![0u8; length as usize];
+    r.read_exact(&mut buf).await?;
+    Ok(buf)
+}
+
+/// Reads a varint from an async reader.
+///
+/// Returns `Ok(None)` if EOF is hit before any byte of the varint is read
+/// (i.e. a clean block-boundary EOF); any other truncation is an error.
+async fn read_varint<R: AsyncRead + Unpin>(r: &mut R) -> Result<Option<u64>> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    let mut first = true;
+
+    loop {
+        let mut byte = [0u8; 1];
+        match r.read(&mut byte).await? {
+            0 if first => return Ok(None),
+            0 => return Err(anyhow::anyhow!("Unexpected EOF reading varint")),
+            _ => {}
+        }
+
+        let b = byte[0];
+        value |= ((b & 0x7F) as u64) << shift;
+        if b & 0x80 == 0 {
+            return Ok(Some(value));
+        }
+        first = false;
+        shift += 7;
+        if shift > 63 {
+            return Err(anyhow::anyhow!("Varint too long"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::Encryption;
+    use crate::encoding::Compression;
+    use crate::writer::InMemoryCarBuilder;
+    use futures::io::Cursor as AsyncCursor;
+    use futures::StreamExt;
+
+    fn build_car() -> Vec<u8> {
+        let mut builder = InMemoryCarBuilder::new(Compression::None);
+        builder.add_row(&"a".to_string(), &b"1".to_vec()).unwrap();
+        builder.add_row(&"b".to_string(), &b"2".to_vec()).unwrap();
+        let (bytes, _index) = builder.finalize().unwrap();
+        bytes
+    }
+
+    #[test]
+    fn reads_rows_one_at_a_time_via_next_row() {
+        futures::executor::block_on(async {
+            let bytes = build_car();
+            let mut reader = CarReader::new(AsyncCursor::new(bytes)).await.unwrap();
+            assert_eq!(reader.header().roots.len(), 1);
+
+            let mut rows = Vec::new();
+            while let Some(row) = reader.next_row().await.unwrap() {
+                rows.push(row);
+            }
+            assert_eq!(
+                rows,
+                vec![("a".to_string(), b"1".to_vec()), ("b".to_string(), b"2".to_vec())]
+            );
+        });
+    }
+
+    #[test]
+    fn into_stream_yields_the_same_rows() {
+        futures::executor::block_on(async {
+            let bytes = build_car();
+            let reader = CarReader::new(AsyncCursor::new(bytes)).await.unwrap();
+            let rows: Vec<_> = reader.into_stream().map(|r| r.unwrap()).collect().await;
+            assert_eq!(
+                rows,
+                vec![("a".to_string(), b"1".to_vec()), ("b".to_string(), b"2".to_vec())]
+            );
+        });
+    }
+
+    #[test]
+    fn rejects_encrypted_headers() {
+        futures::executor::block_on(async {
+            let mut builder = InMemoryCarBuilder::new(Compression::None)
+                .with_encryption(Encryption::AesGcm256, "s3cret")
+                .unwrap();
+            builder.add_row(&"a".to_string(), &b"1".to_vec()).unwrap();
+            let (bytes, _index) = builder.finalize().unwrap();
+
+            let err = CarReader::new(AsyncCursor::new(bytes)).await.unwrap_err();
+            assert!(err.to_string().contains("does not support encrypted"));
+        });
+    }
+
+    #[test]
+    fn detects_a_corrupted_row_block() {
+        futures::executor::block_on(async {
+            let mut bytes = build_car();
+            let last = bytes.len() - 1;
+            bytes[last] ^= 0xFF;
+
+            let mut reader = CarReader::new(AsyncCursor::new(bytes)).await.unwrap();
+            let err = loop {
+                match reader.next_row().await {
+                    Ok(Some(_)) => continue,
+                    Ok(None) => panic!("expected a CID mismatch error"),
+                    Err(e) => break e,
+                }
+            };
+            assert!(err.to_string().contains("CID mismatch"));
+        });
+    }
+}
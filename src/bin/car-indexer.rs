@@ -18,7 +18,7 @@ fn main() -> Result<()> {
     let mut reader = BufReader::new(file);
 
     // Generate the index from the CAR reader
-    let index = generate_index_from_car_reader(&mut reader)?;
+    let index = generate_index_from_car_reader(&mut reader, None)?;
 
     // Print the index
     for (row_key, offset, length) in index {
@@ -17,7 +17,7 @@ fn main() -> Result<()> {
     let file = File::open(car_path)?;
     let mut reader = BufReader::new(file);
 
-    let read_rows = read_all_rows_from_car_reader(&mut reader)?;
+    let read_rows = read_all_rows_from_car_reader(&mut reader, None)?;
 
     for (key, data) in read_rows {
         println!(
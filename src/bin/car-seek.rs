@@ -20,7 +20,7 @@ fn main() -> Result<()> {
     let mut reader = BufReader::new(file);
 
     // Read the block at the specified offset and length
-    let (row_key, row_data) = read_block_at_offset_reader(&mut reader, offset, length)?;
+    let (row_key, row_data) = read_block_at_offset_reader(&mut reader, offset, length, None)?;
 
     // Print the row key and data
     println!("Row Key: {}", row_key);
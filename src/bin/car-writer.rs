@@ -1,4 +1,4 @@
-use dexter_ipfs_car::{write_multiple_rows_as_car};
+use dexter_ipfs_car::{write_multiple_rows_as_car, Compression};
 use std::env;
 use std::io::{self, BufRead};
 
@@ -29,7 +29,7 @@ fn main() -> anyhow::Result<()> {
         std::process::exit(1);
     }
 
-    write_multiple_rows_as_car(car_path, &rows)?;
+    write_multiple_rows_as_car(car_path, &rows, Compression::None)?;
 
     println!("Done writing {}", car_path);
 
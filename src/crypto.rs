@@ -0,0 +1,110 @@
+use anyhow::{anyhow, Result};
+use argon2::Argon2;
+use rand::RngCore;
+
+pub(crate) const SALT_LEN: usize = 16;
+pub(crate) const NONCE_LEN: usize = 12;
+pub(crate) const KEY_LEN: usize = 32;
+
+/// Authenticated encryption applied to row payloads, for CARs that hold
+/// sensitive data and should not be stored at rest in the clear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encryption {
+    AesGcm256,
+    ChaCha20Poly1305,
+}
+
+impl Encryption {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Encryption::AesGcm256 => "aes-gcm-256",
+            Encryption::ChaCha20Poly1305 => "chacha20poly1305",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "aes-gcm-256" => Ok(Encryption::AesGcm256),
+            "chacha20poly1305" => Ok(Encryption::ChaCha20Poly1305),
+            other => Err(anyhow!("unknown encryption algorithm: {other}")),
+        }
+    }
+
+    pub(crate) fn encrypt(
+        &self,
+        key: &[u8; KEY_LEN],
+        nonce: &[u8; NONCE_LEN],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>> {
+        match self {
+            Encryption::AesGcm256 => {
+                use aes_gcm::aead::Aead;
+                use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce};
+                let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+                cipher
+                    .encrypt(Nonce::from_slice(nonce), plaintext)
+                    .map_err(|_| anyhow!("AEAD encryption failed"))
+            }
+            Encryption::ChaCha20Poly1305 => {
+                use chacha20poly1305::aead::Aead;
+                use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce};
+                let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+                cipher
+                    .encrypt(Nonce::from_slice(nonce), plaintext)
+                    .map_err(|_| anyhow!("AEAD encryption failed"))
+            }
+        }
+    }
+
+    /// Decrypts `ciphertext`, returning a distinct AEAD-authentication error
+    /// on tag mismatch. A tag mismatch alone does not tell corrupted data
+    /// from a wrong passphrase; the full-file readers verify each block's
+    /// `Cid` before decrypting, so there corruption is reported as a CID
+    /// mismatch instead.
+    pub(crate) fn decrypt(
+        &self,
+        key: &[u8; KEY_LEN],
+        nonce: &[u8; NONCE_LEN],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>> {
+        let result = match self {
+            Encryption::AesGcm256 => {
+                use aes_gcm::aead::Aead;
+                use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce};
+                let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+                cipher.decrypt(Nonce::from_slice(nonce), ciphertext)
+            }
+            Encryption::ChaCha20Poly1305 => {
+                use chacha20poly1305::aead::Aead;
+                use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce};
+                let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+                cipher.decrypt(Nonce::from_slice(nonce), ciphertext)
+            }
+        };
+
+        result.map_err(|_| {
+            anyhow!("AEAD authentication failed: data is corrupted, or the passphrase is wrong")
+        })
+    }
+}
+
+pub(crate) fn random_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    salt
+}
+
+pub(crate) fn random_nonce() -> [u8; NONCE_LEN] {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    nonce
+}
+
+/// Derives a 32-byte key from `passphrase` and `salt` using Argon2id.
+pub(crate) fn derive_key(passphrase: &str, salt: &[u8; SALT_LEN]) -> Result<[u8; KEY_LEN]> {
+    let mut key = [0u8; KEY_LEN];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("key derivation failed: {e}"))?;
+    Ok(key)
+}
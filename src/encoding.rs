@@ -1,4 +1,6 @@
-use anyhow::Result;
+use std::io::{Read, Write};
+
+use anyhow::{anyhow, Result};
 use cid::multihash::{Code, MultihashDigest};
 use cid::Cid;
 use serde::{Serialize, Deserialize};
@@ -12,20 +14,159 @@ struct EncodedRow {
     data: RowData,
 }
 
-/// Encodes `(RowKey, RowData)` into CBOR and returns `(Cid, Bytes)`.
-pub fn encode_row(key: &RowKey, data: &RowData) -> Result<(Cid, Vec<u8>)> {
+/// The compression codec applied to a row's CBOR payload before it's written
+/// to a block. Each block stores its own 1-byte codec tag, so different rows
+/// in one CAR file can use different settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// No compression; the CBOR bytes are stored as-is (besides the tag byte).
+    None,
+    /// Zstandard at the given compression level.
+    Zstd(i32),
+    Bzip2,
+    Lzma,
+}
+
+impl Compression {
+    fn tag(&self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Zstd(_) => 1,
+            Compression::Bzip2 => 2,
+            Compression::Lzma => 3,
+        }
+    }
+
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(data.to_vec()),
+            Compression::Zstd(level) => Ok(zstd::stream::encode_all(data, *level)?),
+            Compression::Bzip2 => {
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                encoder.write_all(data)?;
+                Ok(encoder.finish()?)
+            }
+            Compression::Lzma => {
+                let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+                encoder.write_all(data)?;
+                Ok(encoder.finish()?)
+            }
+        }
+    }
+
+    fn decompress(tag: u8, payload: &[u8]) -> Result<Vec<u8>> {
+        match tag {
+            0 => Ok(payload.to_vec()),
+            1 => Ok(zstd::stream::decode_all(payload)?),
+            2 => {
+                let mut decoder = bzip2::read::BzDecoder::new(payload);
+                let mut out = Vec::new();
+                decoder.read_to_end(&mut out)?;
+                Ok(out)
+            }
+            3 => {
+                let mut decoder = xz2::read::XzDecoder::new(payload);
+                let mut out = Vec::new();
+                decoder.read_to_end(&mut out)?;
+                Ok(out)
+            }
+            other => Err(anyhow!("unknown compression codec tag: {other}")),
+        }
+    }
+}
+
+/// Encodes `(RowKey, RowData)` into CBOR, compresses it with `compression`,
+/// and returns `(Cid, Bytes)`. The returned bytes are `[1-byte codec tag |
+/// compressed bytes]`; the `Cid` is computed over that full tagged payload
+/// so integrity still verifies end-to-end.
+pub fn encode_row(key: &RowKey, data: &RowData, compression: Compression) -> Result<(Cid, Vec<u8>)> {
     let to_encode = EncodedRow {
         key: key.clone(),
         data: data.clone(),
     };
     let cbor_data = to_vec(&to_encode)?;
-    let hash = Code::Sha2_256.digest(&cbor_data);
+    let compressed = compression.compress(&cbor_data)?;
+
+    let mut tagged = Vec::with_capacity(1 + compressed.len());
+    tagged.push(compression.tag());
+    tagged.extend_from_slice(&compressed);
+
+    let hash = Code::Sha2_256.digest(&tagged);
     let cid = Cid::new_v1(0x71, hash); // 0x71 = dag-cbor
-    Ok((cid, cbor_data))
+    Ok((cid, tagged))
 }
 
-/// Decodes bytes (CBOR) into `(RowKey, RowData)`.
+/// Decodes a row block (as produced by [`encode_row`]) into `(RowKey, RowData)`.
+///
+/// For backward compatibility, a block whose first byte is `0xA2` (the CBOR
+/// major-type/length byte for a 2-entry map, which is exactly what a legacy,
+/// uncompressed `EncodedRow` starts with) is treated as legacy data with no
+/// codec tag at all.
 pub fn decode_row(bytes: &[u8]) -> Result<(RowKey, RowData)> {
-    let decoded: EncodedRow = from_slice(bytes)?;
+    if bytes.first() == Some(&0xA2) {
+        let decoded: EncodedRow = from_slice(bytes)?;
+        return Ok((decoded.key, decoded.data));
+    }
+
+    let (tag, payload) = bytes
+        .split_first()
+        .ok_or_else(|| anyhow!("empty row block"))?;
+    let cbor_data = Compression::decompress(*tag, payload)?;
+    let decoded: EncodedRow = from_slice(&cbor_data)?;
     Ok((decoded.key, decoded.data))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(compression: Compression) {
+        let key: RowKey = "row-1".to_string();
+        let data: RowData = b"some row payload bytes".to_vec();
+
+        let (_cid, bytes) = encode_row(&key, &data, compression).unwrap();
+        let (decoded_key, decoded_data) = decode_row(&bytes).unwrap();
+
+        assert_eq!(decoded_key, key);
+        assert_eq!(decoded_data, data);
+    }
+
+    #[test]
+    fn round_trips_uncompressed() {
+        round_trip(Compression::None);
+    }
+
+    #[test]
+    fn round_trips_zstd() {
+        round_trip(Compression::Zstd(3));
+    }
+
+    #[test]
+    fn round_trips_bzip2() {
+        round_trip(Compression::Bzip2);
+    }
+
+    #[test]
+    fn round_trips_lzma() {
+        round_trip(Compression::Lzma);
+    }
+
+    #[test]
+    fn decodes_legacy_untagged_rows() {
+        // Pre-compression rows were stored as plain CBOR with no codec tag
+        // byte at all; `decode_row` detects this via the leading 0xA2 map
+        // marker instead of a codec tag.
+        let key: RowKey = "legacy-row".to_string();
+        let data: RowData = b"legacy payload".to_vec();
+        let legacy_bytes = to_vec(&EncodedRow {
+            key: key.clone(),
+            data: data.clone(),
+        })
+        .unwrap();
+
+        let (decoded_key, decoded_data) = decode_row(&legacy_bytes).unwrap();
+        assert_eq!(decoded_key, key);
+        assert_eq!(decoded_data, data);
+    }
+}
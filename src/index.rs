@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+
+use crate::reader::{read_block_at_offset_with_key, DecryptionKey};
+use crate::writer::BlockIndexEntry;
+use crate::{RowData, RowKey};
+
+/// A persistent sidecar index (`<car>.idx`) mapping each row's key to its
+/// `(offset, length)` in a CAR file, loaded once and then giving O(1)
+/// lookup by [`RowKey`] instead of requiring the caller to already know the
+/// offset, or rescanning the whole file.
+pub struct CarIndex {
+    entries: HashMap<RowKey, (u64, u64)>,
+}
+
+impl CarIndex {
+    /// Loads a `.idx` sidecar file written by
+    /// [`crate::writer::CarWriter::finalize`].
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        let mut reader = BufReader::new(file);
+
+        let count = read_u64(&mut reader)?;
+        let mut entries = HashMap::with_capacity(count as usize);
+        for _ in 0..count {
+            let key_len = read_varint(&mut reader)?;
+            let mut key_buf = vec![0u8; key_len as usize];
+            reader.read_exact(&mut key_buf)?;
+            let row_key = String::from_utf8(key_buf)?;
+
+            let offset = read_u64(&mut reader)?;
+            let length = read_u64(&mut reader)?;
+            entries.insert(row_key, (offset, length));
+        }
+
+        Ok(CarIndex { entries })
+    }
+
+    /// Looks up a row's `(offset, length)` by key.
+    pub fn get(&self, key: &RowKey) -> Option<(u64, u64)> {
+        self.entries.get(key).copied()
+    }
+
+    /// The number of rows in the index.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Looks up `key` in `index` and reads the row straight from `reader` at the
+/// recorded offset, without scanning the file.
+///
+/// `decryption_key` must be `Some` if the CAR file was written with
+/// encryption-at-rest enabled, and `None` otherwise: a key that is passed
+/// is always applied to the block, so reading an unencrypted file with one
+/// fails. [`DecryptionKey::derive`] returns `None` for an unencrypted file. Deriving a
+/// [`DecryptionKey`] is Argon2id-slow by design, so callers doing many
+/// lookups against the same file should derive it once with
+/// [`DecryptionKey::derive`] before the loop and pass it to every call here,
+/// instead of re-deriving it per lookup — which would defeat the point of an
+/// O(1) index.
+pub fn read_row_by_key<R: Read + Seek>(
+    reader: &mut R,
+    index: &CarIndex,
+    key: &RowKey,
+    decryption_key: Option<&DecryptionKey>,
+) -> Result<(RowKey, RowData)> {
+    let (offset, length) = index
+        .get(key)
+        .ok_or_else(|| anyhow!("row key not found in index: {key}"))?;
+    read_block_at_offset_with_key(reader, offset, length, decryption_key)
+}
+
+/// Derives the sidecar index path for a CAR file: `<car>.idx`.
+pub(crate) fn sidecar_path_for(car_path: &Path) -> PathBuf {
+    let mut os_string = car_path.as_os_str().to_owned();
+    os_string.push(".idx");
+    PathBuf::from(os_string)
+}
+
+/// Serializes `entries` into the `.idx` sidecar format: an 8-byte
+/// big-endian entry count, then per entry (sorted by `row_key`)
+/// `[key_len varint | key bytes | offset u64 | length u64]`.
+fn serialize_index(entries: &[BlockIndexEntry]) -> Vec<u8> {
+    let mut sorted: Vec<&BlockIndexEntry> = entries.iter().collect();
+    sorted.sort_by(|a, b| a.row_key.cmp(&b.row_key));
+
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&(sorted.len() as u64).to_be_bytes());
+    for entry in sorted {
+        buf.extend_from_slice(&write_varint_to_vec(entry.row_key.len() as u64));
+        buf.extend_from_slice(entry.row_key.as_bytes());
+        buf.extend_from_slice(&entry.offset.to_be_bytes());
+        buf.extend_from_slice(&entry.length.to_be_bytes());
+    }
+    buf
+}
+
+/// Writes the `.idx` sidecar for `car_path` from `entries`. Skips the write
+/// if the file already holds byte-identical contents, so re-finalizing an
+/// unchanged dataset doesn't churn the file.
+pub(crate) fn write_sidecar_index(car_path: &Path, entries: &[BlockIndexEntry]) -> Result<()> {
+    let bytes = serialize_index(entries);
+    let idx_path = sidecar_path_for(car_path);
+
+    if let Ok(existing) = fs::read(&idx_path) {
+        if existing == bytes {
+            return Ok(());
+        }
+    }
+
+    let mut file = BufWriter::new(File::create(&idx_path)?);
+    file.write_all(&bytes)?;
+    Ok(())
+}
+
+fn read_u64<R: Read>(r: &mut R) -> Result<u64> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_be_bytes(buf))
+}
+
+fn read_varint<R: Read>(r: &mut R) -> Result<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8; 1];
+        r.read_exact(&mut byte)
+            .map_err(|_| anyhow!("Unexpected EOF reading varint"))?;
+        let b = byte[0];
+        value |= ((b & 0x7F) as u64) << shift;
+        if b & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+        if shift > 63 {
+            return Err(anyhow!("Varint too long"));
+        }
+    }
+}
+
+fn write_varint_to_vec(mut value: u64) -> Vec<u8> {
+    let mut buf = [0u8; 10];
+    let mut i = 0;
+    while value >= 0x80 {
+        buf[i] = ((value & 0x7F) as u8) | 0x80;
+        value >>= 7;
+        i += 1;
+    }
+    buf[i] = value as u8;
+    i += 1;
+    buf[..i].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::Compression;
+    use crate::writer::CarWriter;
+
+    /// A directory under the system temp dir, unique per test, cleaned up on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("ipfs-car-index-test-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn round_trips_load_and_lookup_by_key() {
+        let dir = TempDir::new("roundtrip");
+        let car_path = dir.0.join("data.car");
+
+        let mut writer = CarWriter::new(&car_path, Compression::None).unwrap();
+        writer.add_row(&"a".to_string(), &b"1".to_vec()).unwrap();
+        writer.add_row(&"b".to_string(), &b"2".to_vec()).unwrap();
+        writer.finalize().unwrap();
+
+        let index = CarIndex::load(sidecar_path_for(&car_path)).unwrap();
+        assert_eq!(index.len(), 2);
+        assert!(!index.is_empty());
+        assert!(index.get(&"missing".to_string()).is_none());
+
+        let file = File::open(&car_path).unwrap();
+        let mut reader = BufReader::new(file);
+        let (row_key, row_data) = read_row_by_key(&mut reader, &index, &"b".to_string(), None).unwrap();
+        assert_eq!(row_key, "b");
+        assert_eq!(row_data, b"2".to_vec());
+    }
+
+    #[test]
+    fn write_sidecar_index_skips_rewrite_when_unchanged() {
+        let dir = TempDir::new("skip-rewrite");
+        let car_path = dir.0.join("data.car");
+        let idx_path = sidecar_path_for(&car_path);
+
+        let entries = vec![
+            BlockIndexEntry { row_key: "a".to_string(), offset: 10, length: 5 },
+            BlockIndexEntry { row_key: "b".to_string(), offset: 15, length: 7 },
+        ];
+        write_sidecar_index(&car_path, &entries).unwrap();
+
+        // Make the sidecar read-only so a real rewrite attempt would fail;
+        // this proves whether write_sidecar_index actually touched the file.
+        let mut perms = fs::metadata(&idx_path).unwrap().permissions();
+        perms.set_readonly(true);
+        fs::set_permissions(&idx_path, perms.clone()).unwrap();
+
+        // Byte-identical entries: must be skipped, so this succeeds even
+        // though the file can't actually be written to.
+        write_sidecar_index(&car_path, &entries).unwrap();
+
+        // Different entries: a real rewrite is required, which fails against
+        // the read-only file, proving the first call really was skipped.
+        let different = vec![BlockIndexEntry { row_key: "c".to_string(), offset: 1, length: 1 }];
+        assert!(write_sidecar_index(&car_path, &different).is_err());
+
+        perms.set_readonly(false);
+        fs::set_permissions(&idx_path, perms).unwrap();
+    }
+}
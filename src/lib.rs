@@ -1,9 +1,21 @@
 pub mod types;
+pub mod crypto;
 pub mod encoding;
+pub mod manifest;
 pub mod writer;
 pub mod reader;
+pub mod index;
+pub mod async_reader;
+pub mod segmented;
 
 pub use types::*;
-pub use encoding::*;
-pub use writer::{write_multiple_rows_as_car, BlockIndexEntry};
-pub use reader::{read_all_rows_from_car_reader, read_block_at_offset_reader, generate_index_from_car_reader};
\ No newline at end of file
+pub use crypto::Encryption;
+pub use encoding::{encode_row, decode_row, Compression};
+pub use writer::{write_multiple_rows_as_car, CarWriter, InMemoryCarBuilder, BlockIndexEntry};
+pub use reader::{
+    read_all_rows_from_car_reader, read_block_at_offset_reader, read_block_at_offset_with_key,
+    generate_index_from_car_reader, DecryptionKey,
+};
+pub use index::{CarIndex, read_row_by_key};
+pub use async_reader::{CarHeader, CarReader};
+pub use segmented::{SegmentedCarStore, SegmentMeta, SegmentThreshold};
\ No newline at end of file
@@ -0,0 +1,44 @@
+use anyhow::Result;
+use cid::multihash::{Code, MultihashDigest};
+use cid::Cid;
+use serde::{Deserialize, Serialize};
+
+/// The single dag-cbor block a CAR's lone root `Cid` points to: the list of
+/// child row-block CIDs, in write order. This lets one root represent an
+/// arbitrary number of row blocks instead of requiring one root per block,
+/// and is what lets [`crate::reader::read_all_rows_from_car_reader`] and
+/// [`crate::reader::generate_index_from_car_reader`] iterate blocks directly
+/// instead of assuming one block per root.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Manifest {
+    /// Child row-block CIDs, rendered as strings (consistent with how the
+    /// CAR header itself stores `roots`).
+    pub(crate) links: Vec<String>,
+}
+
+impl Manifest {
+    pub(crate) fn new(links: Vec<Cid>) -> Self {
+        Manifest {
+            links: links.iter().map(Cid::to_string).collect(),
+        }
+    }
+
+    pub(crate) fn cids(&self) -> Result<Vec<Cid>> {
+        self.links.iter().map(|s| Ok(s.parse()?)).collect()
+    }
+}
+
+/// Encodes `manifest` as a dag-cbor block and returns `(Cid, Bytes)`. Unlike
+/// row blocks, the manifest is structural metadata, not row payload, so it
+/// is never compressed or encrypted.
+pub(crate) fn encode_manifest(manifest: &Manifest) -> Result<(Cid, Vec<u8>)> {
+    let bytes = serde_cbor::to_vec(manifest)?;
+    let hash = Code::Sha2_256.digest(&bytes);
+    let cid = Cid::new_v1(0x71, hash);
+    Ok((cid, bytes))
+}
+
+/// Decodes a manifest block (as produced by [`encode_manifest`]).
+pub(crate) fn decode_manifest(bytes: &[u8]) -> Result<Manifest> {
+    Ok(serde_cbor::from_slice(bytes)?)
+}
@@ -1,9 +1,118 @@
 use std::io::{self, Read, Seek, SeekFrom, Cursor};
 use anyhow::{Result, anyhow};
+use cid::multihash::{Code, MultihashDigest};
 use cid::Cid;
 
 use crate::{RowKey, RowData};
+use crate::crypto::{Encryption, KEY_LEN, NONCE_LEN, SALT_LEN};
 use crate::encoding::decode_row;
+use crate::manifest::decode_manifest;
+
+/// The parsed CAR header: `roots`/`version` plus, when the file was written
+/// with encryption-at-rest enabled, the `salt` and algorithm name needed to
+/// re-derive the key from a passphrase.
+struct CarHeader {
+    roots: Vec<String>,
+    #[allow(dead_code)]
+    version: u64,
+    salt: Option<Vec<u8>>,
+    encryption: Option<String>,
+}
+
+/// Key material derived from a passphrase, if the CAR header says the file
+/// is encrypted. Deriving this is Argon2id-slow by design, so callers doing
+/// more than one read against the same file (e.g. repeated
+/// [`crate::index::read_row_by_key`] lookups) should derive it once with
+/// [`DecryptionKey::derive`] and reuse it, rather than passing a raw
+/// passphrase to every call.
+pub struct DecryptionKey {
+    algo: Encryption,
+    key: [u8; KEY_LEN],
+}
+
+impl DecryptionKey {
+    /// Derives the decryption key for `reader`'s CAR header, if any. Reads
+    /// the header from the start of `reader` and restores its prior
+    /// position, so this can be called on a reader already positioned
+    /// mid-file. Returns `Ok(None)` if the file isn't encrypted.
+    pub fn derive<R: Read + Seek>(reader: &mut R, passphrase: Option<&str>) -> Result<Option<Self>> {
+        let header = header_for_offset_read(reader)?;
+        Self::for_header(&header, passphrase)
+    }
+
+    fn for_header(header: &CarHeader, passphrase: Option<&str>) -> Result<Option<Self>> {
+        let (Some(salt), Some(algo_name)) = (&header.salt, &header.encryption) else {
+            return Ok(None);
+        };
+        let passphrase = passphrase.ok_or_else(|| {
+            anyhow!("this CAR file is encrypted; a passphrase is required to read it")
+        })?;
+
+        let salt: [u8; SALT_LEN] = salt
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow!("invalid salt length in CAR header"))?;
+        let algo = Encryption::from_name(algo_name)?;
+        let key = crate::crypto::derive_key(passphrase, &salt)?;
+        Ok(Some(DecryptionKey { algo, key }))
+    }
+
+    /// Splits `[nonce | ciphertext]` off `block` and decrypts it back to the
+    /// plaintext row bytes expected by [`decode_row`].
+    fn open<'a>(&self, block: &'a [u8]) -> Result<std::borrow::Cow<'a, [u8]>> {
+        if block.len() < NONCE_LEN {
+            return Err(anyhow!("encrypted block shorter than a nonce"));
+        }
+        let (nonce_bytes, ciphertext) = block.split_at(NONCE_LEN);
+        let nonce: [u8; NONCE_LEN] = nonce_bytes.try_into()?;
+        let plaintext = self.algo.decrypt(&self.key, &nonce, ciphertext)?;
+        Ok(std::borrow::Cow::Owned(plaintext))
+    }
+}
+
+/// Recomputes the `Cid` that `data` actually hashes to (dag-cbor, SHA2-256),
+/// the same way [`crate::encoding::encode_row`]/[`crate::manifest::encode_manifest`]/
+/// `seal_block` compute it when writing.
+///
+/// The CID bytes physically stored in a block are attacker- (or bitrot-)
+/// controlled, just like the rest of the block, so comparing two CIDs both
+/// sourced from the same untrusted block proves nothing: re-deriving the
+/// digest from the actual bytes received is the only check that detects
+/// corruption confined to the data portion of a block.
+fn recompute_cid(data: &[u8]) -> Cid {
+    let hash = Code::Sha2_256.digest(data);
+    Cid::new_v1(0x71, hash)
+}
+
+/// Verifies that `data` actually hashes to `expected`, erroring with
+/// `context` (e.g. `"manifest"` or `"row block 3"`) if not.
+fn verify_block_cid(data: &[u8], expected: Cid, context: &str) -> Result<()> {
+    let actual = recompute_cid(data);
+    if actual != expected {
+        return Err(anyhow!(
+            "CID mismatch for {}: expected {}, got {}",
+            context, expected, actual
+        ));
+    }
+    Ok(())
+}
+
+/// Reads the manifest block immediately following the header and returns its
+/// list of child row-block CIDs, verifying the block's contents actually
+/// hash to the header's single root.
+fn read_manifest<R: Read>(reader: &mut R, header: &CarHeader) -> Result<Vec<Cid>> {
+    let expected_cid: Cid = header
+        .roots
+        .first()
+        .ok_or_else(|| anyhow!("No roots found in CAR file"))?
+        .parse()?;
+
+    let manifest_block = read_prefixed_block(reader)?;
+    let (_stored_cid, data) = split_cid_and_data(&manifest_block)?;
+    verify_block_cid(&data, expected_cid, "manifest")?;
+
+    decode_manifest(&data)?.cids()
+}
 
 /// Reads **all rows** from an already-opened CAR reader.
 ///
@@ -13,73 +122,106 @@ use crate::encoding::decode_row;
 /// - A custom adapter that reads from HDFS
 /// - An in-memory buffer, etc.
 ///
+/// The CAR's single root points at a dag-cbor manifest block listing the
+/// child row-block CIDs; this reads that manifest first and then loops
+/// reading length-prefixed blocks until EOF, decoding each as a row.
+///
+/// `passphrase` must be `Some` if the CAR file was written with
+/// encryption-at-rest enabled; it is ignored otherwise.
 pub fn read_all_rows_from_car_reader<R: Read + Seek>(
-    reader: &mut R
+    reader: &mut R,
+    passphrase: Option<&str>,
 ) -> Result<Vec<(RowKey, RowData)>> {
     // 1) Read the CAR header block
     let header_bytes = read_prefixed_block(reader)?;
-    let (roots, _version) = parse_header(&header_bytes)?;
+    let header = parse_header(&header_bytes)?;
+    let decryption_key = DecryptionKey::for_header(&header, passphrase)?;
 
-    if roots.is_empty() {
-        return Err(anyhow!("No roots found in CAR file"));
-    }
+    // 2) Read the manifest block the root points at
+    let manifest_cids = read_manifest(reader, &header)?;
 
-    // 2) For each root, read the next block and decode it
+    // 3) Read every row block until EOF, checking each one's CID against
+    //    the manifest so truncation, reordering, or corruption is caught.
     let mut rows = Vec::new();
-    for root_cid_str in &roots {
-        let expected_cid: Cid = root_cid_str.parse()?;
-        let block_bytes = read_prefixed_block(reader)?;
-        let (cid, data) = split_cid_and_data(&block_bytes)?;
-
-        if cid != expected_cid {
-            return Err(anyhow!(
-                "CID mismatch: expected {}, got {}",
-                expected_cid, cid
-            ));
-        }
+    while let Some(block_bytes) = try_read_prefixed_block(reader)? {
+        let (_stored_cid, data) = split_cid_and_data(&block_bytes)?;
+        let expected_cid = expect_manifest_cid(&manifest_cids, rows.len())?;
+        verify_block_cid(&data, expected_cid, &format!("row block {}", rows.len()))?;
 
-        // 3) Our CAR block contains a (row_key, row_data) that we decode:
-        let (row_key, row_data) = decode_row(&data)?;
+        let row_bytes = match &decryption_key {
+            Some(dk) => dk.open(&data)?,
+            None => std::borrow::Cow::Borrowed(&data[..]),
+        };
+        let (row_key, row_data) = decode_row(&row_bytes)?;
         rows.push((row_key, row_data));
     }
 
+    check_manifest_fully_consumed(&manifest_cids, rows.len())?;
     Ok(rows)
 }
 
-/// Generates an index for **all blocks** in a CAR file (by reading them sequentially).
-/// Returns `(RowKey, offset, length)` for each block.
+/// Looks up the CID the manifest expects at `index`, erroring if the file
+/// has more row blocks than the manifest lists.
+fn expect_manifest_cid(manifest_cids: &[Cid], index: usize) -> Result<Cid> {
+    manifest_cids
+        .get(index)
+        .copied()
+        .ok_or_else(|| anyhow!("CAR file has more row blocks than its manifest lists"))
+}
+
+/// Errors if fewer row blocks were actually read than the manifest lists,
+/// i.e. the file is truncated.
+fn check_manifest_fully_consumed(manifest_cids: &[Cid], rows_read: usize) -> Result<()> {
+    if rows_read != manifest_cids.len() {
+        return Err(anyhow!(
+            "manifest lists {} row block(s) but the file only has {}",
+            manifest_cids.len(),
+            rows_read
+        ));
+    }
+    Ok(())
+}
+
+/// Generates an index for **all row blocks** in a CAR file (by reading them
+/// sequentially). Returns `(RowKey, offset, length)` for each row block; the
+/// manifest block itself is not indexed.
 ///
 /// The caller provides an `R` that implements `Read + Seek`.
+///
+/// `passphrase` must be `Some` if the CAR file was written with
+/// encryption-at-rest enabled; it is ignored otherwise.
 pub fn generate_index_from_car_reader<R: Read + Seek>(
-    reader: &mut R
+    reader: &mut R,
+    passphrase: Option<&str>,
 ) -> Result<Vec<(RowKey, u64, u64)>> {
-    // Remember our start offset so we can compute block offsets
-    let mut current_offset = reader.seek(SeekFrom::Current(0))?;
-
     // 1) Read CAR header
     let header_bytes = read_prefixed_block(reader)?;
-    let (roots, _version) = parse_header(&header_bytes)?;
-    if roots.is_empty() {
-        return Err(anyhow!("No roots found in CAR file"));
-    }
+    let header = parse_header(&header_bytes)?;
+    let decryption_key = DecryptionKey::for_header(&header, passphrase)?;
 
-    // 2) After reading the header, `reader` is at the start of the first block
+    // 2) Read the manifest block the root points at (not indexed)
+    let manifest_cids = read_manifest(reader, &header)?;
+    let mut current_offset = reader.stream_position()?;
+
+    // 3) Walk every row block until EOF, checking each one's CID against
+    //    the manifest so truncation, reordering, or corruption is caught.
     let mut index = Vec::new();
-    for _root_cid_str in &roots {
-        // read_block_with_offset returns (offset, total_length, block_bytes)
-        let (offset, length, block_bytes) = read_block_with_offset(reader, current_offset)?;
-        let (_cid, data) = split_cid_and_data(&block_bytes)?;
+    while let Some((offset, length, block_bytes)) = try_read_block_with_offset(reader, current_offset)? {
+        let (_stored_cid, data) = split_cid_and_data(&block_bytes)?;
+        let expected_cid = expect_manifest_cid(&manifest_cids, index.len())?;
+        verify_block_cid(&data, expected_cid, &format!("row block {}", index.len()))?;
 
-        // Convert that data into (row_key, row_data)
-        let (row_key, _row_data) = decode_row(&data)?;
+        let row_bytes = match &decryption_key {
+            Some(dk) => dk.open(&data)?,
+            None => std::borrow::Cow::Borrowed(&data[..]),
+        };
+        let (row_key, _row_data) = decode_row(&row_bytes)?;
 
-        // Push it into our index
         index.push((row_key, offset, length));
-
-        // Move offset forward
         current_offset += length;
     }
 
+    check_manifest_fully_consumed(&manifest_cids, index.len())?;
     Ok(index)
 }
 
@@ -91,10 +233,36 @@ pub fn generate_index_from_car_reader<R: Read + Seek>(
 /// - Seeks `reader` to the given offset
 /// - Reads exactly `length` bytes
 /// - Parses the varint, extracts the CID, then extracts the row data
+///
+/// `passphrase` must be `Some` if the CAR file was written with
+/// encryption-at-rest enabled; it is ignored otherwise.
 pub fn read_block_at_offset_reader<R: Read + Seek>(
     reader: &mut R,
     offset: u64,
     length: u64,
+    passphrase: Option<&str>,
+) -> Result<(RowKey, RowData)> {
+    // Always check the header, even when `passphrase` is `None`: if the file
+    // is encrypted and no passphrase was given, `DecryptionKey::derive` errors
+    // out here instead of letting raw ciphertext fall through to `decode_row`
+    // as if it were plaintext. Callers making many lookups against the same
+    // file should derive the key once with `DecryptionKey::derive` and call
+    // `read_block_at_offset_with_key` directly instead, since this derives
+    // the (deliberately slow) Argon2id key fresh on every call.
+    let decryption_key = DecryptionKey::derive(reader, passphrase)?;
+    read_block_at_offset_with_key(reader, offset, length, decryption_key.as_ref())
+}
+
+/// Same as [`read_block_at_offset_reader`], but takes an already-derived
+/// [`DecryptionKey`] (or `None` for an unencrypted file) instead of deriving
+/// one from a passphrase on every call. Intended for callers doing repeated
+/// by-offset reads against the same CAR file, such as
+/// [`crate::index::read_row_by_key`].
+pub fn read_block_at_offset_with_key<R: Read + Seek>(
+    reader: &mut R,
+    offset: u64,
+    length: u64,
+    decryption_key: Option<&DecryptionKey>,
 ) -> Result<(RowKey, RowData)> {
     // 1) Seek to `offset`
     reader.seek(SeekFrom::Start(offset))?;
@@ -105,13 +273,30 @@ pub fn read_block_at_offset_reader<R: Read + Seek>(
 
     // 3) Within that buffer, the first part is a varint length
     //    that says how many bytes belong to the CID+data.
-    let (cid, data) = split_cid_and_data_from_block(&block_buf)?;
+    let (_cid, data) = split_cid_and_data_from_block(&block_buf)?;
+
+    // 4) Decrypt (if needed), then decode the row
+    let row_bytes = match decryption_key {
+        Some(dk) => dk.open(&data)?,
+        None => std::borrow::Cow::Borrowed(&data[..]),
+    };
 
-    // 4) Decode row
-    let (row_key, row_data) = decode_row(&data)?;
+    let (row_key, row_data) = decode_row(&row_bytes)?;
     Ok((row_key, row_data))
 }
 
+/// Re-reads the CAR header from the start of `reader` so a passphrase can be
+/// applied to a single offset read without the caller having to carry the
+/// header around themselves, then restores the reader's prior position.
+fn header_for_offset_read<R: Read + Seek>(reader: &mut R) -> Result<CarHeader> {
+    let saved_pos = reader.stream_position()?;
+    reader.seek(SeekFrom::Start(0))?;
+    let header_bytes = read_prefixed_block(reader)?;
+    let header = parse_header(&header_bytes)?;
+    reader.seek(SeekFrom::Start(saved_pos))?;
+    Ok(header)
+}
+
 
 /// Reads a varint-prefixed block from the reader.
 fn read_prefixed_block<R: Read>(r: &mut R) -> Result<Vec<u8>> {
@@ -121,20 +306,46 @@ fn read_prefixed_block<R: Read>(r: &mut R) -> Result<Vec<u8>> {
     Ok(buf)
 }
 
+/// Reads a varint-prefixed block from the reader, returning `Ok(None)` on a
+/// clean EOF (no bytes of a new block's length prefix could be read).
+fn try_read_prefixed_block<R: Read>(r: &mut R) -> Result<Option<Vec<u8>>> {
+    let length = match try_read_varint(r)? {
+        Some(length) => length,
+        None => return Ok(None),
+    };
+    let mut buf = vec![0u8; length as usize];
+    r.read_exact(&mut buf)?;
+    Ok(Some(buf))
+}
+
 /// Reads a varint from the reader.
 fn read_varint<R: Read>(r: &mut R) -> Result<u64> {
+    match try_read_varint(r)? {
+        Some(value) => Ok(value),
+        None => Err(anyhow!("Unexpected EOF reading varint")),
+    }
+}
+
+/// Reads a varint from the reader, returning `Ok(None)` if EOF is hit before
+/// any byte of the varint could be read (a clean block-boundary EOF); any
+/// other truncation is an error.
+fn try_read_varint<R: Read>(r: &mut R) -> Result<Option<u64>> {
     let mut value = 0u64;
     let mut shift = 0;
+    let mut first = true;
     loop {
         let mut byte = [0u8; 1];
-        if r.read_exact(&mut byte).is_err() {
-            return Err(anyhow!("Unexpected EOF reading varint"));
+        match r.read(&mut byte)? {
+            0 if first => return Ok(None),
+            0 => return Err(anyhow!("Unexpected EOF reading varint")),
+            _ => {}
         }
         let b = byte[0];
         value |= ((b & 0x7F) as u64) << shift;
         if b & 0x80 == 0 {
-            return Ok(value);
+            return Ok(Some(value));
         }
+        first = false;
         shift += 7;
         if shift > 63 {
             return Err(anyhow!("Varint too long"));
@@ -142,20 +353,22 @@ fn read_varint<R: Read>(r: &mut R) -> Result<u64> {
     }
 }
 
-/// Reads a varint prefix for the upcoming CID+data, returning offset/length/block.
-/// offset: where the block starts
-/// length: total length (including varint prefix + CID+data)
-fn read_block_with_offset<R: Read + Seek>(
+/// Reads a varint-prefixed block at `start_offset`, returning
+/// `(offset, total_length, block_bytes)`, or `Ok(None)` on a clean EOF.
+/// `total_length` includes the varint prefix + CID + data.
+fn try_read_block_with_offset<R: Read + Seek>(
     r: &mut R,
     start_offset: u64,
-) -> Result<(u64, u64, Vec<u8>)> {
-    let offset = start_offset;
-    let length_value = read_varint(r)?; // how many bytes for CID+data
+) -> Result<Option<(u64, u64, Vec<u8>)>> {
+    let length_value = match try_read_varint(r)? {
+        Some(length) => length,
+        None => return Ok(None),
+    };
     let mut buf = vec![0; length_value as usize];
     r.read_exact(&mut buf)?;
-    let end_offset = r.seek(SeekFrom::Current(0))?;
-    let total_length = end_offset - offset;
-    Ok((offset, total_length, buf))
+    let end_offset = r.stream_position()?;
+    let total_length = end_offset - start_offset;
+    Ok(Some((start_offset, total_length, buf)))
 }
 
 /// Splits a block into (Cid, data) given `block` (already excludes the varint prefix).
@@ -178,15 +391,147 @@ fn split_cid_and_data_from_block(block_with_prefix: &[u8]) -> Result<(Cid, Vec<u
     split_cid_and_data(&cid_data_buf)
 }
 
-/// Parse the CAR header (CBOR-encoded {"roots": [...], "version": ... }).
-fn parse_header(bytes: &[u8]) -> Result<(Vec<String>, u64)> {
+/// Parse the CAR header (CBOR-encoded `{roots, version, salt?, encryption?}`).
+fn parse_header(bytes: &[u8]) -> Result<CarHeader> {
     use serde::Deserialize;
     #[derive(Deserialize)]
-    struct CarHeader {
+    struct RawCarHeader {
         roots: Vec<String>,
         version: u64,
+        #[serde(default)]
+        salt: Option<Vec<u8>>,
+        #[serde(default)]
+        encryption: Option<String>,
+    }
+
+    let raw: RawCarHeader = serde_cbor::from_slice(bytes)?;
+    Ok(CarHeader {
+        roots: raw.roots,
+        version: raw.version,
+        salt: raw.salt,
+        encryption: raw.encryption,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::Encryption;
+    use crate::encoding::Compression;
+    use crate::writer::InMemoryCarBuilder;
+
+    fn build_encrypted_car(algo: Encryption, passphrase: &str) -> Vec<u8> {
+        let mut builder = InMemoryCarBuilder::new(Compression::None)
+            .with_encryption(algo, passphrase)
+            .unwrap();
+        builder.add_row(&"k1".to_string(), &b"hello".to_vec()).unwrap();
+        builder.add_row(&"k2".to_string(), &b"world".to_vec()).unwrap();
+        let (bytes, _index) = builder.finalize().unwrap();
+        bytes
     }
 
-    let ch: CarHeader = serde_cbor::from_slice(bytes)?;
-    Ok((ch.roots, ch.version))
-}
\ No newline at end of file
+    #[test]
+    fn round_trips_with_correct_passphrase() {
+        let bytes = build_encrypted_car(Encryption::AesGcm256, "correct horse battery staple");
+        let mut reader = Cursor::new(bytes);
+        let rows = read_all_rows_from_car_reader(&mut reader, Some("correct horse battery staple")).unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                ("k1".to_string(), b"hello".to_vec()),
+                ("k2".to_string(), b"world".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trips_with_chacha20poly1305() {
+        let bytes = build_encrypted_car(Encryption::ChaCha20Poly1305, "passphrase");
+        let mut reader = Cursor::new(bytes);
+        let rows = read_all_rows_from_car_reader(&mut reader, Some("passphrase")).unwrap();
+        assert_eq!(rows, vec![("k1".to_string(), b"hello".to_vec()), ("k2".to_string(), b"world".to_vec())]);
+    }
+
+    #[test]
+    fn errors_without_a_passphrase() {
+        let bytes = build_encrypted_car(Encryption::AesGcm256, "s3cret");
+        let mut reader = Cursor::new(bytes);
+        let err = read_all_rows_from_car_reader(&mut reader, None).unwrap_err();
+        assert!(err.to_string().contains("passphrase is required"));
+    }
+
+    #[test]
+    fn errors_with_wrong_passphrase() {
+        let bytes = build_encrypted_car(Encryption::AesGcm256, "s3cret");
+        let mut reader = Cursor::new(bytes);
+        let err = read_all_rows_from_car_reader(&mut reader, Some("not-it")).unwrap_err();
+        assert!(err.to_string().contains("AEAD authentication failed"));
+    }
+
+    #[test]
+    fn round_trips_unencrypted_rows_via_the_manifest() {
+        let mut builder = InMemoryCarBuilder::new(Compression::None);
+        builder.add_row(&"a".to_string(), &b"1".to_vec()).unwrap();
+        builder.add_row(&"b".to_string(), &b"2".to_vec()).unwrap();
+        let (bytes, _index) = builder.finalize().unwrap();
+
+        let mut reader = Cursor::new(bytes);
+        let rows = read_all_rows_from_car_reader(&mut reader, None).unwrap();
+        assert_eq!(
+            rows,
+            vec![("a".to_string(), b"1".to_vec()), ("b".to_string(), b"2".to_vec())]
+        );
+    }
+
+    #[test]
+    fn detects_a_corrupted_row_block() {
+        let mut builder = InMemoryCarBuilder::new(Compression::None);
+        builder.add_row(&"a".to_string(), &b"1".to_vec()).unwrap();
+        builder.add_row(&"b".to_string(), &b"2".to_vec()).unwrap();
+        let (mut bytes, _index) = builder.finalize().unwrap();
+
+        // Flip a byte near the end of the file, inside the last row block's
+        // data, without touching its length prefix. The manifest still lists
+        // the original CID, so this must be caught as a CID mismatch rather
+        // than silently returning corrupted row data.
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+
+        let mut reader = Cursor::new(bytes);
+        let err = read_all_rows_from_car_reader(&mut reader, None).unwrap_err();
+        assert!(err.to_string().contains("CID mismatch"));
+    }
+
+    #[test]
+    fn detects_a_truncated_file() {
+        let mut builder = InMemoryCarBuilder::new(Compression::None);
+        builder.add_row(&"a".to_string(), &b"1".to_vec()).unwrap();
+        builder.add_row(&"b".to_string(), &b"2".to_vec()).unwrap();
+        let (bytes, index) = builder.finalize().unwrap();
+
+        // Drop the final row block entirely so the manifest lists more
+        // blocks than the file actually has.
+        let truncated = bytes[..index[0].offset as usize + index[0].length as usize].to_vec();
+        // Re-include the manifest + header + first row block only.
+        let mut reader = Cursor::new(truncated);
+        let err = read_all_rows_from_car_reader(&mut reader, None).unwrap_err();
+        assert!(err.to_string().contains("manifest lists"));
+    }
+
+    #[test]
+    fn read_block_at_offset_reader_errors_without_a_passphrase() {
+        // Regression test: offset reads used to skip the "encrypted but no
+        // passphrase" guard entirely when `passphrase` was `None`.
+        let mut builder = InMemoryCarBuilder::new(Compression::None)
+            .with_encryption(Encryption::AesGcm256, "s3cret")
+            .unwrap();
+        builder.add_row(&"k1".to_string(), &b"hello".to_vec()).unwrap();
+        let (bytes, index) = builder.finalize().unwrap();
+        let entry = &index[0];
+
+        let mut reader = Cursor::new(bytes);
+        let err = read_block_at_offset_reader(&mut reader, entry.offset, entry.length, None).unwrap_err();
+        assert!(err.to_string().contains("passphrase is required"));
+    }
+}
@@ -0,0 +1,336 @@
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::encoding::Compression;
+use crate::reader::read_all_rows_from_car_reader;
+use crate::writer::CarWriter;
+use crate::{RowData, RowKey};
+
+/// When a segment should roll over to a new file: once it holds at least
+/// one row and crosses either threshold.
+#[derive(Debug, Clone, Copy)]
+pub struct SegmentThreshold {
+    pub max_bytes: u64,
+    pub max_rows: u64,
+}
+
+impl Default for SegmentThreshold {
+    fn default() -> Self {
+        SegmentThreshold {
+            max_bytes: 128 * 1024 * 1024,
+            max_rows: 1_000_000,
+        }
+    }
+}
+
+/// A segment's entry in the store's manifest: its filename, key range, row
+/// count, and byte size, enough for `scan_range` to decide whether to open
+/// it at all.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentMeta {
+    pub file_name: String,
+    pub min_key: RowKey,
+    pub max_key: RowKey,
+    pub row_count: u64,
+    pub byte_size: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    segments: Vec<SegmentMeta>,
+}
+
+/// An append-only store for time-keyed rows that writes rows in append order,
+/// spilling across rolling segment CAR files once a configurable byte or row
+/// threshold is crossed. Keys are compared as plain strings; no timestamp is
+/// parsed out of a `RowKey`, so segment key ranges follow the keys' string
+/// order. This avoids rewriting a monolithic
+/// header on every append and, via `scan_range`, lets a range read over a
+/// large time-ordered dataset skip any segment whose key range can't
+/// possibly contain the query.
+pub struct SegmentedCarStore {
+    dir: PathBuf,
+    threshold: SegmentThreshold,
+    compression: Compression,
+    manifest: Manifest,
+    current_writer: Option<CarWriter>,
+    current_meta: Option<SegmentMeta>,
+}
+
+impl SegmentedCarStore {
+    /// Opens (or creates) a segmented store rooted at `dir`, loading its
+    /// manifest if one already exists.
+    pub fn open<P: AsRef<Path>>(
+        dir: P,
+        threshold: SegmentThreshold,
+        compression: Compression,
+    ) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let manifest = Self::load_manifest(&dir)?;
+
+        Ok(SegmentedCarStore {
+            dir,
+            threshold,
+            compression,
+            manifest,
+            current_writer: None,
+            current_meta: None,
+        })
+    }
+
+    /// Appends a row, writing it into the current open segment and rolling
+    /// to a new segment first if the configured threshold has been crossed.
+    pub fn append_row(&mut self, key: &RowKey, data: &RowData) -> Result<()> {
+        if self.should_roll(key, data) {
+            self.roll_segment()?;
+        }
+        if self.current_writer.is_none() {
+            self.open_new_segment()?;
+        }
+
+        self.current_writer
+            .as_mut()
+            .expect("segment just opened")
+            .add_row(key, data)?;
+
+        let meta = self.current_meta.as_mut().expect("segment just opened");
+        let is_first_row = meta.row_count == 0;
+        meta.row_count += 1;
+        meta.byte_size += estimated_row_size(key, data);
+        if is_first_row || *key < meta.min_key {
+            meta.min_key = key.clone();
+        }
+        if is_first_row || *key > meta.max_key {
+            meta.max_key = key.clone();
+        }
+
+        Ok(())
+    }
+
+    /// Finalizes the current segment (if any) and flushes the manifest to
+    /// disk. A further `append_row` call opens a fresh segment.
+    pub fn flush(&mut self) -> Result<()> {
+        self.roll_segment()
+    }
+
+    /// Reads every row whose key falls within `[start_key, end_key]`
+    /// (inclusive), in key order. Segments whose `[min_key, max_key]` range
+    /// doesn't overlap the query are never opened.
+    ///
+    /// The current segment is flushed first, so rows appended since the last
+    /// roll are included rather than being invisible until the next roll or
+    /// `flush()`.
+    pub fn scan_range(&mut self, start_key: &RowKey, end_key: &RowKey) -> Result<Vec<(RowKey, RowData)>> {
+        self.flush()?;
+
+        let mut rows = Vec::new();
+
+        for segment in &self.manifest.segments {
+            if segment.max_key < *start_key || segment.min_key > *end_key {
+                continue; // segment's key range can't overlap the query
+            }
+
+            let path = self.dir.join(&segment.file_name);
+            let file = File::open(&path)?;
+            let mut reader = BufReader::new(file);
+            let segment_rows = read_all_rows_from_car_reader(&mut reader, None)?;
+
+            rows.extend(
+                segment_rows
+                    .into_iter()
+                    .filter(|(row_key, _)| row_key >= start_key && row_key <= end_key),
+            );
+        }
+
+        rows.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(rows)
+    }
+
+    fn should_roll(&self, key: &RowKey, data: &RowData) -> bool {
+        let Some(meta) = &self.current_meta else {
+            return false;
+        };
+        if meta.row_count == 0 {
+            return false; // never roll an empty segment, no matter how big the next row is
+        }
+        meta.row_count >= self.threshold.max_rows
+            || meta.byte_size + estimated_row_size(key, data) > self.threshold.max_bytes
+    }
+
+    fn open_new_segment(&mut self) -> Result<()> {
+        let file_name = format!("segment-{:06}.car", self.manifest.segments.len());
+        let path = self.dir.join(&file_name);
+
+        self.current_writer = Some(CarWriter::new(&path, self.compression)?);
+        self.current_meta = Some(SegmentMeta {
+            file_name,
+            min_key: RowKey::new(),
+            max_key: RowKey::new(),
+            row_count: 0,
+            byte_size: 0,
+        });
+        Ok(())
+    }
+
+    /// Finalizes the current segment (if any), records it in the manifest,
+    /// and writes the manifest out atomically (write-to-temp then rename)
+    /// so a crash mid-roll never leaves a corrupt manifest.
+    fn roll_segment(&mut self) -> Result<()> {
+        let (Some(writer), Some(meta)) = (self.current_writer.take(), self.current_meta.take())
+        else {
+            return Ok(());
+        };
+
+        writer.finalize()?;
+        self.manifest.segments.push(meta);
+        self.write_manifest_atomic()
+    }
+
+    fn manifest_path(dir: &Path) -> PathBuf {
+        dir.join("segments.manifest")
+    }
+
+    fn load_manifest(dir: &Path) -> Result<Manifest> {
+        let path = Self::manifest_path(dir);
+        if !path.exists() {
+            return Ok(Manifest::default());
+        }
+        let bytes = fs::read(path)?;
+        Ok(serde_cbor::from_slice(&bytes)?)
+    }
+
+    fn write_manifest_atomic(&self) -> Result<()> {
+        let path = Self::manifest_path(&self.dir);
+        let tmp_path = path.with_extension("tmp");
+        let bytes = serde_cbor::to_vec(&self.manifest)?;
+        fs::write(&tmp_path, &bytes)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+}
+
+impl Drop for SegmentedCarStore {
+    /// Best-effort safety net: the current segment is only finalized and
+    /// recorded in the manifest by `flush()`/`roll_segment()`, so a caller
+    /// that appends rows and never calls `flush()` would otherwise lose the
+    /// entire open segment silently. Errors here can't be propagated, so
+    /// they're logged instead of swallowed; callers that need to observe
+    /// flush failures should still call `flush()` explicitly.
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            eprintln!("SegmentedCarStore: failed to flush current segment on drop: {e}");
+        }
+    }
+}
+
+fn estimated_row_size(key: &RowKey, data: &RowData) -> u64 {
+    (key.len() + data.len()) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directory under the system temp dir, unique per test, cleaned up on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "ipfs-car-segmented-test-{name}-{}",
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn rolls_into_multiple_segments_once_the_row_threshold_is_crossed() {
+        let dir = TempDir::new("rolls");
+        let threshold = SegmentThreshold {
+            max_bytes: u64::MAX,
+            max_rows: 2,
+        };
+        let mut store = SegmentedCarStore::open(&dir.0, threshold, Compression::None).unwrap();
+
+        for i in 0..5 {
+            let key = format!("k{i:03}");
+            store.append_row(&key, &b"row".to_vec()).unwrap();
+        }
+        store.flush().unwrap();
+
+        assert_eq!(store.manifest.segments.len(), 3);
+    }
+
+    #[test]
+    fn scan_range_sees_rows_still_in_the_open_segment() {
+        // Regression test: scan_range used to only consult already-rolled
+        // segments, so rows appended since the last roll were invisible
+        // until flush()/roll was triggered.
+        let dir = TempDir::new("open-segment");
+        let threshold = SegmentThreshold::default();
+        let mut store = SegmentedCarStore::open(&dir.0, threshold, Compression::None).unwrap();
+
+        store.append_row(&"k001".to_string(), &b"hello".to_vec()).unwrap();
+        store.append_row(&"k002".to_string(), &b"world".to_vec()).unwrap();
+
+        let rows = store
+            .scan_range(&"k000".to_string(), &"k999".to_string())
+            .unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                ("k001".to_string(), b"hello".to_vec()),
+                ("k002".to_string(), b"world".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_range_filters_by_key_and_skips_non_overlapping_segments() {
+        let dir = TempDir::new("filters");
+        let threshold = SegmentThreshold {
+            max_bytes: u64::MAX,
+            max_rows: 1,
+        };
+        let mut store = SegmentedCarStore::open(&dir.0, threshold, Compression::None).unwrap();
+
+        store.append_row(&"a".to_string(), &b"1".to_vec()).unwrap();
+        store.append_row(&"m".to_string(), &b"2".to_vec()).unwrap();
+        store.append_row(&"z".to_string(), &b"3".to_vec()).unwrap();
+
+        let rows = store.scan_range(&"b".to_string(), &"y".to_string()).unwrap();
+        assert_eq!(rows, vec![("m".to_string(), b"2".to_vec())]);
+    }
+
+    #[test]
+    fn drop_flushes_the_open_segment_so_it_is_not_lost() {
+        let dir = TempDir::new("drop-flush");
+        let threshold = SegmentThreshold::default();
+        {
+            let mut store = SegmentedCarStore::open(&dir.0, threshold, Compression::None).unwrap();
+            store.append_row(&"k001".to_string(), &b"hello".to_vec()).unwrap();
+            // Dropped without an explicit flush() call.
+        }
+
+        let mut reopened = SegmentedCarStore::open(&dir.0, threshold, Compression::None).unwrap();
+        let rows = reopened
+            .scan_range(&"k000".to_string(), &"k999".to_string())
+            .unwrap();
+        assert_eq!(rows, vec![("k001".to_string(), b"hello".to_vec())]);
+    }
+}
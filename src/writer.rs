@@ -1,13 +1,16 @@
 use std::fs::File;
-use std::io::{Write, BufWriter, Seek, SeekFrom, Cursor};
-use std::path::Path;
+use std::io::{Write, BufWriter, Seek, Cursor};
+use std::path::{Path, PathBuf};
 use anyhow::Result;
+use cid::multihash::{Code, MultihashDigest};
 use cid::Cid;
 use serde::Serialize;
 use serde_cbor::to_vec;
 
 use crate::{RowKey, RowData};
-use crate::encoding::encode_row;
+use crate::crypto::{self, Encryption, KEY_LEN, NONCE_LEN, SALT_LEN};
+use crate::encoding::{encode_row, Compression};
+use crate::manifest::{encode_manifest, Manifest};
 
 /// An index entry that maps a row_key to its (offset, length) in the CAR file.
 #[derive(Debug)]
@@ -17,54 +20,147 @@ pub struct BlockIndexEntry {
     pub length: u64,
 }
 
+/// Derived encryption state held for the lifetime of a single CAR file write.
+struct EncryptionState {
+    algo: Encryption,
+    salt: [u8; SALT_LEN],
+    key: [u8; KEY_LEN],
+}
+
+impl EncryptionState {
+    fn new(algo: Encryption, passphrase: &str) -> Result<Self> {
+        let salt = crypto::random_salt();
+        let key = crypto::derive_key(passphrase, &salt)?;
+        Ok(EncryptionState { algo, salt, key })
+    }
+}
+
+/// Encrypts `block_data` (if `encryption` is set) and returns the `Cid`
+/// computed over the bytes that will actually be written to the block,
+/// so integrity still verifies end-to-end.
+fn seal_block(
+    cid: Cid,
+    block_data: Vec<u8>,
+    encryption: Option<&EncryptionState>,
+) -> Result<(Cid, Vec<u8>)> {
+    let Some(enc) = encryption else {
+        return Ok((cid, block_data));
+    };
+
+    let nonce = crypto::random_nonce();
+    let ciphertext = enc.algo.encrypt(&enc.key, &nonce, &block_data)?;
+
+    let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+
+    let hash = Code::Sha2_256.digest(&sealed);
+    let sealed_cid = Cid::new_v1(0x71, hash);
+    Ok((sealed_cid, sealed))
+}
+
+/// Writes a single varint-length-prefixed `[CID bytes | data]` block and
+/// returns its total on-disk length (prefix + CID + data).
+fn write_block<W: Write>(writer: &mut W, cid: &Cid, data: &[u8]) -> Result<u64> {
+    let mut block_buf = Vec::new();
+    block_buf.extend_from_slice(&cid.to_bytes());
+    block_buf.extend_from_slice(data);
+
+    let length_bytes = write_varint_to_vec(block_buf.len() as u64);
+    writer.write_all(&length_bytes)?;
+    writer.write_all(&block_buf)?;
+
+    Ok(length_bytes.len() as u64 + block_buf.len() as u64)
+}
+
 /// CAR file writer
 pub struct CarWriter {
+    path: PathBuf,
     writer: BufWriter<File>,
+    compression: Compression,
+    encryption: Option<EncryptionState>,
     cids: Vec<(RowKey, Cid, Vec<u8>)>,
 }
 
 impl CarWriter {
-    /// Create a new CarWriter that writes to the given path on local filesystem.
-    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let file = File::create(path)?;
+    /// Create a new CarWriter that writes to the given path on local filesystem,
+    /// compressing rows with `compression` unless overridden per-row via
+    /// [`CarWriter::add_row_with_compression`].
+    pub fn new<P: AsRef<Path>>(path: P, compression: Compression) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::create(&path)?;
         Ok(CarWriter {
+            path,
             writer: BufWriter::new(file),
+            compression,
+            encryption: None,
             cids: Vec::new(),
         })
     }
 
-    /// Add a row to the CAR file's internal buffer (not yet written).
+    /// Enable authenticated encryption-at-rest for every row written by this
+    /// CarWriter, deriving a key from `passphrase` via Argon2id. A random
+    /// salt is generated now and stored in the CAR header so the same
+    /// passphrase can re-derive the key on read.
+    pub fn with_encryption(mut self, algo: Encryption, passphrase: &str) -> Result<Self> {
+        self.encryption = Some(EncryptionState::new(algo, passphrase)?);
+        Ok(self)
+    }
+
+    /// Add a row to the CAR file's internal buffer (not yet written), using
+    /// this writer's default compression codec.
     pub fn add_row(&mut self, key: &RowKey, data: &RowData) -> Result<()> {
-        let (cid, block_data) = encode_row(key, data)?;
+        self.add_row_with_compression(key, data, self.compression)
+    }
+
+    /// Add a row, compressing it with `compression` instead of this writer's
+    /// default. Different rows in the same file may use different codecs.
+    pub fn add_row_with_compression(
+        &mut self,
+        key: &RowKey,
+        data: &RowData,
+        compression: Compression,
+    ) -> Result<()> {
+        let (cid, block_data) = encode_row(key, data, compression)?;
         self.cids.push((key.clone(), cid, block_data));
         Ok(())
     }
 
     /// Finalize and write the CAR file to disk.
-    /// Returns an index of `(row_key, offset, length)` for each block.
+    ///
+    /// The file's single root is a dag-cbor manifest block listing every row
+    /// block's `Cid`, in write order; the row blocks themselves follow it.
+    /// This decouples block count from root count and keeps the file
+    /// interoperable with other CAR tooling, unlike declaring every block
+    /// its own root.
+    ///
+    /// Returns an index of `(row_key, offset, length)` for each row block
+    /// (the manifest block itself is not indexed).
     pub fn finalize(mut self) -> Result<Vec<BlockIndexEntry>> {
-        self.write_header()?;
+        // Seal every row block first so the manifest can link to the CIDs
+        // that will actually end up on disk. `mem::take` swaps `self.cids`
+        // out for an empty `Vec` rather than moving it out of `self`, so
+        // `self` stays fully initialized for the `&mut self` calls below.
+        let sealed_blocks = std::mem::take(&mut self.cids)
+            .into_iter()
+            .map(|(row_key, cid, block_data)| {
+                let (cid, block_data) = seal_block(cid, block_data, self.encryption.as_ref())?;
+                Ok((row_key, cid, block_data))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let manifest = Manifest::new(sealed_blocks.iter().map(|(_, cid, _)| *cid).collect());
+        let (manifest_cid, manifest_bytes) = encode_manifest(&manifest)?;
+
+        self.write_header(manifest_cid)?;
+        write_block(&mut self.writer, &manifest_cid, &manifest_bytes)?;
 
         let mut index = Vec::new();
-        let mut current_offset = self.writer.seek(SeekFrom::Current(0))?;
+        let mut current_offset = self.writer.stream_position()?;
 
-        for (row_key, cid, block_data) in self.cids {
-            // The start offset before writing this block
+        for (row_key, cid, block_data) in sealed_blocks {
             let block_start_offset = current_offset;
-
-            // Prepare block data: [CID bytes | row data]
-            let mut block_buf = Vec::new();
-            block_buf.extend_from_slice(&cid.to_bytes());
-            block_buf.extend_from_slice(&block_data);
-
-            // Write varint (block length) + block contents
-            let length_bytes = write_varint_to_vec(block_buf.len() as u64);
-            self.writer.write_all(&length_bytes)?;
-            self.writer.write_all(&block_buf)?;
-
-            let block_total_length = length_bytes.len() as u64 + block_buf.len() as u64;
-
-            // Update offset
+            let block_total_length = write_block(&mut self.writer, &cid, &block_data)?;
             current_offset += block_total_length;
 
             index.push(BlockIndexEntry {
@@ -75,24 +171,12 @@ impl CarWriter {
         }
 
         self.writer.flush()?;
+        crate::index::write_sidecar_index(&self.path, &index)?;
         Ok(index)
     }
 
-    fn write_header(&mut self) -> Result<()> {
-        #[derive(Serialize)]
-        struct CarHeader {
-            roots: Vec<String>,
-            version: u64,
-        }
-
-        // Each block is considered a "root" for simplicity
-        let root_strings: Vec<String> =
-            self.cids.iter().map(|(_, cid, _)| cid.to_string()).collect();
-
-        let header = CarHeader {
-            roots: root_strings,
-            version: 1,
-        };
+    fn write_header(&mut self, manifest_cid: Cid) -> Result<()> {
+        let header = build_car_header(vec![manifest_cid.to_string()], self.encryption.as_ref());
 
         let header_bytes = to_vec(&header)?;
         let length_bytes = write_varint_to_vec(header_bytes.len() as u64);
@@ -107,47 +191,83 @@ impl CarWriter {
 /// Build CAR file in memory
 pub struct InMemoryCarBuilder {
     buffer: Cursor<Vec<u8>>,
+    compression: Compression,
+    encryption: Option<EncryptionState>,
     cids: Vec<(RowKey, Cid, Vec<u8>)>,
 }
 
 impl InMemoryCarBuilder {
-    /// Create an in-memory CarBuilder that writes to a buffer (Vec<u8>).
-    pub fn new() -> Self {
+    /// Create an in-memory CarBuilder that writes to a buffer (Vec<u8>),
+    /// compressing rows with `compression` unless overridden per-row via
+    /// [`InMemoryCarBuilder::add_row_with_compression`].
+    pub fn new(compression: Compression) -> Self {
         InMemoryCarBuilder {
             buffer: Cursor::new(Vec::new()),
+            compression,
+            encryption: None,
             cids: Vec::new(),
         }
     }
 
-    /// Add a row to the in-memory buffer (not yet written).
+    /// Enable authenticated encryption-at-rest for every row written by this
+    /// builder, deriving a key from `passphrase` via Argon2id. A random salt
+    /// is generated now and stored in the CAR header so the same passphrase
+    /// can re-derive the key on read.
+    pub fn with_encryption(mut self, algo: Encryption, passphrase: &str) -> Result<Self> {
+        self.encryption = Some(EncryptionState::new(algo, passphrase)?);
+        Ok(self)
+    }
+
+    /// Add a row to the in-memory buffer (not yet written), using this
+    /// builder's default compression codec.
     pub fn add_row(&mut self, key: &RowKey, data: &RowData) -> Result<()> {
-        let (cid, block_data) = encode_row(key, data)?;
+        self.add_row_with_compression(key, data, self.compression)
+    }
+
+    /// Add a row, compressing it with `compression` instead of this
+    /// builder's default. Different rows in the same file may use different
+    /// codecs.
+    pub fn add_row_with_compression(
+        &mut self,
+        key: &RowKey,
+        data: &RowData,
+        compression: Compression,
+    ) -> Result<()> {
+        let (cid, block_data) = encode_row(key, data, compression)?;
         self.cids.push((key.clone(), cid, block_data));
         Ok(())
     }
 
     /// Finalize and build the CAR file in memory.
-    /// Returns `(car_bytes, index)`.
+    ///
+    /// As with [`CarWriter::finalize`], the single root is a dag-cbor
+    /// manifest block listing every row block's `Cid`; the row blocks
+    /// follow it. Returns `(car_bytes, index)`, where the index does not
+    /// include the manifest block.
     pub fn finalize(mut self) -> Result<(Vec<u8>, Vec<BlockIndexEntry>)> {
-        self.write_header()?;
+        // `mem::take` swaps `self.cids` out for an empty `Vec` rather than
+        // moving it out of `self`, so `self` stays fully initialized for the
+        // `&mut self` calls below.
+        let sealed_blocks = std::mem::take(&mut self.cids)
+            .into_iter()
+            .map(|(row_key, cid, block_data)| {
+                let (cid, block_data) = seal_block(cid, block_data, self.encryption.as_ref())?;
+                Ok((row_key, cid, block_data))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let manifest = Manifest::new(sealed_blocks.iter().map(|(_, cid, _)| *cid).collect());
+        let (manifest_cid, manifest_bytes) = encode_manifest(&manifest)?;
+
+        self.write_header(manifest_cid)?;
+        write_block(&mut self.buffer, &manifest_cid, &manifest_bytes)?;
 
         let mut index = Vec::new();
-        let mut current_offset = self.buffer.seek(SeekFrom::Current(0))?;
+        let mut current_offset = self.buffer.stream_position()?;
 
-        for (row_key, cid, block_data) in self.cids {
+        for (row_key, cid, block_data) in sealed_blocks {
             let block_start_offset = current_offset;
-
-            // Build the block
-            let mut block_buf = Vec::new();
-            block_buf.extend_from_slice(&cid.to_bytes());
-            block_buf.extend_from_slice(&block_data);
-
-            // varint length
-            let length_bytes = write_varint_to_vec(block_buf.len() as u64);
-            self.buffer.write_all(&length_bytes)?;
-            self.buffer.write_all(&block_buf)?;
-
-            let block_total_length = length_bytes.len() as u64 + block_buf.len() as u64;
+            let block_total_length = write_block(&mut self.buffer, &cid, &block_data)?;
             current_offset += block_total_length;
 
             index.push(BlockIndexEntry {
@@ -162,20 +282,8 @@ impl InMemoryCarBuilder {
         Ok((final_data, index))
     }
 
-    fn write_header(&mut self) -> Result<()> {
-        #[derive(Serialize)]
-        struct CarHeader {
-            roots: Vec<String>,
-            version: u64,
-        }
-
-        let root_strings: Vec<String> =
-            self.cids.iter().map(|(_, cid, _)| cid.to_string()).collect();
-
-        let header = CarHeader {
-            roots: root_strings,
-            version: 1,
-        };
+    fn write_header(&mut self, manifest_cid: Cid) -> Result<()> {
+        let header = build_car_header(vec![manifest_cid.to_string()], self.encryption.as_ref());
 
         let header_bytes = to_vec(&header)?;
         let length_bytes = write_varint_to_vec(header_bytes.len() as u64);
@@ -187,13 +295,38 @@ impl InMemoryCarBuilder {
     }
 }
 
+/// The CAR header written by [`CarWriter`] and [`InMemoryCarBuilder`].
+///
+/// `roots` always contains exactly one entry: the manifest block's `Cid`.
+/// `salt` and `encryption` are only present when encryption-at-rest is
+/// enabled; readers use them to re-derive the key from a supplied
+/// passphrase.
+#[derive(Serialize)]
+pub(crate) struct CarHeader {
+    pub(crate) roots: Vec<String>,
+    pub(crate) version: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) salt: Option<Vec<u8>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) encryption: Option<String>,
+}
+
+fn build_car_header(roots: Vec<String>, encryption: Option<&EncryptionState>) -> CarHeader {
+    CarHeader {
+        roots,
+        version: 1,
+        salt: encryption.map(|enc| enc.salt.to_vec()),
+        encryption: encryption.map(|enc| enc.algo.name().to_string()),
+    }
+}
 
 /// A convenience function for writing multiple rows directly to a file.
 pub fn write_multiple_rows_as_car<P: AsRef<std::path::Path>>(
     path: P,
     rows: &[(RowKey, RowData)],
+    compression: Compression,
 ) -> Result<Vec<BlockIndexEntry>> {
-    let mut writer = CarWriter::new(path)?;
+    let mut writer = CarWriter::new(path, compression)?;
     for (key, data) in rows {
         writer.add_row(key, data)?;
     }
@@ -204,8 +337,9 @@ pub fn write_multiple_rows_as_car<P: AsRef<std::path::Path>>(
 /// A convenience function for building a CAR file entirely in memory
 pub fn build_in_memory_car(
     rows: &[(RowKey, RowData)],
+    compression: Compression,
 ) -> Result<(Vec<u8>, Vec<BlockIndexEntry>)> {
-    let mut builder = InMemoryCarBuilder::new();
+    let mut builder = InMemoryCarBuilder::new(compression);
     for (key, data) in rows {
         builder.add_row(key, data)?;
     }
@@ -225,4 +359,4 @@ fn write_varint_to_vec(mut value: u64) -> Vec<u8> {
     buf[i] = value as u8;
     i += 1;
     buf[..i].to_vec()
-}
\ No newline at end of file
+}